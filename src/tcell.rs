@@ -2,6 +2,7 @@ use std::any::TypeId;
 use std::cell::UnsafeCell;
 use std::collections::HashSet;
 use std::marker::PhantomData;
+use std::mem;
 use std::sync::Mutex;
 
 lazy_static! {
@@ -52,6 +53,80 @@ impl<Q: 'static> TCellOwner<Q> {
         unsafe { &mut *qc.value.get() }
     }
 
+    /// Store a new value in a `TCell`, dropping the old value.
+    #[inline]
+    pub fn set<T>(&mut self, tc: &TCell<Q, T>, val: T) {
+        unsafe {
+            *tc.value.get() = val;
+        }
+    }
+
+    /// Store a new value in a `TCell`, returning the old value.
+    #[inline]
+    pub fn replace<T>(&mut self, tc: &TCell<Q, T>, val: T) -> T {
+        unsafe { mem::replace(&mut *tc.value.get(), val) }
+    }
+
+    /// Take the value out of a `TCell`, leaving `T::default()` in its
+    /// place and returning the old value.
+    #[inline]
+    pub fn take<T: Default>(&mut self, tc: &TCell<Q, T>) -> T {
+        self.replace(tc, T::default())
+    }
+
+    /// Borrow the contents of a `TCell` mutably for the duration of
+    /// the given closure, returning whatever the closure returns.
+    #[inline]
+    pub fn update<T, R>(&mut self, tc: &TCell<Q, T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(unsafe { &mut *tc.value.get() })
+    }
+
+    /// Swap the contents of two `TCell` instances.  Panics if the two
+    /// `TCell` instances point to the same memory.
+    #[inline]
+    pub fn swap<T>(&mut self, a: &TCell<Q, T>, b: &TCell<Q, T>) {
+        assert!(
+            a as *const _ as usize != b as *const _ as usize,
+            "Illegal to swap a TCell with itself with swap()"
+        );
+        unsafe {
+            mem::swap(&mut *a.value.get(), &mut *b.value.get());
+        }
+    }
+
+    /// Borrow the contents of a `TOnceCell` immutably, returning
+    /// `None` if it has not yet been initialized.
+    #[inline]
+    pub fn get_once<'a, T>(&'a self, cell: &'a TOnceCell<Q, T>) -> Option<&'a T> {
+        unsafe { (*cell.value.get()).as_ref() }
+    }
+
+    /// Borrow the contents of a `TOnceCell` immutably, initializing it
+    /// with the value returned by `f` if it is currently empty.  Only
+    /// one `TCellOwner<Q>` can exist per marker type, so a shared
+    /// borrow of the owner is enough to prove there are no concurrent
+    /// borrows of the cell, making the one-time write sound.  Once
+    /// populated, the stored value is never moved or overwritten, so
+    /// any references handed out stay valid.
+    #[inline]
+    pub fn get_or_init_once<'a, T>(
+        &'a self,
+        cell: &'a TOnceCell<Q, T>,
+        f: impl FnOnce() -> T,
+    ) -> &'a T {
+        // Evaluate `f()` before taking any `&mut` into the cell, so a
+        // reentrant `get_or_init_once`/`get_once` on the same cell can
+        // never alias a live `&mut` or be clobbered by this store.
+        if self.get_once(cell).is_none() {
+            let val = f();
+            let slot = unsafe { &mut *cell.value.get() };
+            if slot.is_none() {
+                *slot = Some(val);
+            }
+        }
+        self.get_once(cell).unwrap()
+    }
+
     /// Borrow contents of two `TCell` instances mutably.  Panics if
     /// the two `TCell` instances point to the same memory.
     #[inline]
@@ -90,6 +165,36 @@ impl<Q: 'static> TCellOwner<Q> {
             )
         }
     }
+
+    /// Borrow contents of `N` `TCell` instances mutably, returning an
+    /// array of exclusive references.  Panics if any pair of `TCell`
+    /// instances point to the same memory.
+    pub fn get_muts<'a, T, const N: usize>(
+        &'a mut self,
+        cells: [&'a TCell<Q, T>; N],
+    ) -> [&'a mut T; N] {
+        let addrs = cells.map(|c| c as *const _ as usize);
+        if N <= 8 {
+            for i in 0..N {
+                for j in (i + 1)..N {
+                    assert!(
+                        addrs[i] != addrs[j],
+                        "Illegal to borrow same TCell twice with get_muts()"
+                    );
+                }
+            }
+        } else {
+            let mut sorted = addrs;
+            sorted.sort_unstable();
+            for i in 1..N {
+                assert!(
+                    sorted[i - 1] != sorted[i],
+                    "Illegal to borrow same TCell twice with get_muts()"
+                );
+            }
+        }
+        cells.map(|c| unsafe { &mut *c.value.get() })
+    }
 }
 
 /// Cell whose contents is owned (for borrowing purposes) by a
@@ -117,9 +222,35 @@ impl<Q, T> TCell<Q, T> {
     }
 }
 
+/// Write-once cell whose contents is owned (for borrowing purposes)
+/// by a [`TCellOwner`].
+///
+/// Starts out empty and can be filled exactly once through a shared
+/// borrow of the owner.  After that it only ever hands out shared
+/// references to the stored value.  See [crate
+/// documentation](index.html).
+///
+/// [`TCellOwner`]: struct.TCellOwner.html
+pub struct TOnceCell<Q, T> {
+    owner: PhantomData<Q>,
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<Q, T> TOnceCell<Q, T> {
+    /// Create a new empty `TOnceCell` owned for borrowing purposes by
+    /// the given `TCellOwner<Q>`
+    #[inline]
+    pub const fn new(_owner: &TCellOwner<Q>) -> TOnceCell<Q, T> {
+        TOnceCell {
+            owner: PhantomData,
+            value: UnsafeCell::new(None),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{TCell, TCellOwner};
+    use super::{TCell, TCellOwner, TOnceCell};
     #[test]
     #[should_panic]
     fn tcell_singleton_1() {
@@ -159,4 +290,91 @@ mod tests {
         let total = *c1ref + *c2ref;
         assert_eq!(total, 303);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn tcell_mutate() {
+        struct Marker;
+        type ACellOwner = TCellOwner<Marker>;
+        type ACell<T> = TCell<Marker, T>;
+        let mut owner = ACellOwner::new();
+        let c1 = ACell::new(&owner, 100u32);
+        owner.set(&c1, 50);
+        assert_eq!(*owner.get(&c1), 50);
+        assert_eq!(owner.replace(&c1, 60), 50);
+        assert_eq!(owner.take(&c1), 60);
+        assert_eq!(*owner.get(&c1), 0);
+        let r = owner.update(&c1, |v| {
+            *v += 5;
+            *v * 2
+        });
+        assert_eq!(r, 10);
+        assert_eq!(*owner.get(&c1), 5);
+    }
+
+    #[test]
+    fn tcell_swap() {
+        struct Marker;
+        type ACellOwner = TCellOwner<Marker>;
+        type ACell<T> = TCell<Marker, T>;
+        let mut owner = ACellOwner::new();
+        let c1 = ACell::new(&owner, 1u32);
+        let c2 = ACell::new(&owner, 2u32);
+        owner.swap(&c1, &c2);
+        assert_eq!(*owner.get(&c1), 2);
+        assert_eq!(*owner.get(&c2), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_swap_same() {
+        struct Marker;
+        type ACellOwner = TCellOwner<Marker>;
+        type ACell<T> = TCell<Marker, T>;
+        let mut owner = ACellOwner::new();
+        let c1 = ACell::new(&owner, 1u32);
+        owner.swap(&c1, &c1); // Panic here
+    }
+
+    #[test]
+    fn tcell_once() {
+        struct Marker;
+        type ACellOwner = TCellOwner<Marker>;
+        type AOnceCell<T> = TOnceCell<Marker, T>;
+        let owner = ACellOwner::new();
+        let c1 = AOnceCell::<u32>::new(&owner);
+        assert_eq!(owner.get_once(&c1), None);
+        let r = owner.get_or_init_once(&c1, || 42);
+        assert_eq!(*r, 42);
+        // Second call must not overwrite, and must ignore the closure.
+        let r = owner.get_or_init_once(&c1, || 99);
+        assert_eq!(*r, 42);
+        assert_eq!(owner.get_once(&c1), Some(&42));
+    }
+
+    #[test]
+    fn tcell_get_muts() {
+        struct Marker;
+        type ACellOwner = TCellOwner<Marker>;
+        type ACell<T> = TCell<Marker, T>;
+        let mut owner = ACellOwner::new();
+        let c1 = ACell::new(&owner, 1u32);
+        let c2 = ACell::new(&owner, 2u32);
+        let c3 = ACell::new(&owner, 3u32);
+        let [r1, r2, r3] = owner.get_muts([&c1, &c2, &c3]);
+        *r1 += 10;
+        *r2 += 20;
+        *r3 += 30;
+        assert_eq!((*owner.get(&c1), *owner.get(&c2), *owner.get(&c3)), (11, 22, 33));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tcell_get_muts_same() {
+        struct Marker;
+        type ACellOwner = TCellOwner<Marker>;
+        type ACell<T> = TCell<Marker, T>;
+        let mut owner = ACellOwner::new();
+        let c1 = ACell::new(&owner, 1u32);
+        let _ = owner.get_muts([&c1, &c1]); // Panic here
+    }
+}